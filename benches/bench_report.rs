@@ -0,0 +1,61 @@
+// Optional structured result collector for continuous benchmarking: when enabled,
+// the headline metrics of each step (throughput, p99 latency, logical bytes written,
+// database size) are also recorded here and dumped as a JSON array matching the
+// format consumed by github-action-benchmark, e.g.:
+//   [{ "name": "rocksdb/preload_throughput", "unit": "key/s", "value": 123456.5 }, ...]
+// Per-thread progress lines, the p50/p99.9/max latencies, the CPU figures and the key
+// counts are printed to stdout only and are not recorded.
+
+use std::env;
+use std::fs;
+use std::sync::Mutex;
+
+const OUT_PATH_ENV_VAR: &str = "REDB_BENCH_JSON_OUT";
+
+struct BenchRecord {
+    name: String,
+    unit: String,
+    value: f64,
+}
+
+pub struct BenchReporter {
+    records: Mutex<Vec<BenchRecord>>,
+}
+
+impl BenchReporter {
+    pub fn new() -> Self {
+        Self { records: Mutex::new(Vec::new()) }
+    }
+
+    pub fn enabled() -> bool {
+        env::var(OUT_PATH_ENV_VAR).is_ok()
+    }
+
+    pub fn record(&self, name: &str, unit: &str, value: f64) {
+        self.records.lock().unwrap().push(BenchRecord { name: name.to_string(), unit: unit.to_string(), value });
+    }
+
+    pub fn write_to_file(&self) {
+        let Ok(path) = env::var(OUT_PATH_ENV_VAR) else {
+            return;
+        };
+        let records = self.records.lock().unwrap();
+        fs::write(path, to_json(&records)).unwrap();
+    }
+}
+
+fn to_json(records: &[BenchRecord]) -> String {
+    let mut out = String::from("[\n");
+    for (i, record) in records.iter().enumerate() {
+        out.push_str(&format!(
+            "  {{ \"name\": {:?}, \"unit\": {:?}, \"value\": {} }}",
+            record.name, record.unit, record.value
+        ));
+        if i + 1 < records.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push(']');
+    out
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+use fastrand::Rng;
+
+use crate::key_dist::KeyDistState;
+
+pub const KEY_SIZE: usize = 24;
+
+pub fn create_rng() -> Rng {
+    Rng::with_seed(3)
+}
+
+pub fn gen_key(rng: &mut Rng, dist: &mut KeyDistState) -> Vec<u8> {
+    dist.gen_key(rng)
+}
+
+pub fn gen_prefix(rng: &mut Rng, dist: &mut KeyDistState) -> Vec<u8> {
+    let key = dist.gen_key(rng);
+    key[..KEY_SIZE / 2].to_vec()
+}
+
+pub fn database_size(path: &Path) -> u64 {
+    let mut size = 0u64;
+    for result in walkdir::WalkDir::new(path) {
+        let entry = result.unwrap();
+        size += entry.metadata().unwrap().len();
+    }
+    size
+}
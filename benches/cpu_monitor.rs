@@ -0,0 +1,64 @@
+// Background CPU-utilization sampling, similar to how Solana's ledger-cleanup bench
+// samples CPULoad via systemstat on a timer: lets us report "keys per CPU-second" so
+// a driver that burns cores for marginal throughput doesn't look as good as one that
+// doesn't.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use systemstat::{Platform, System};
+
+pub struct CpuStats {
+    pub mean_user_pct: f64,
+    pub mean_system_pct: f64,
+}
+
+impl CpuStats {
+    pub fn keys_per_cpu_second(&self, keys: u64, duration: Duration) -> Option<f64> {
+        let busy_pct = self.mean_user_pct + self.mean_system_pct;
+        if busy_pct <= 0.0 {
+            return None;
+        }
+        // the aggregate load is a share of all cores together, so scale by the core count
+        let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+        let cpu_seconds = duration.as_secs_f64() * busy_pct / 100.0 * cores;
+        if cpu_seconds <= 0.0 {
+            None
+        } else {
+            Some(keys as f64 / cpu_seconds)
+        }
+    }
+}
+
+// samples CPU user/system utilization roughly every `interval` until `stop` is set.
+// Degrades gracefully (reporting 0% / no samples) on platforms where systemstat can't
+// read CPU load, rather than failing the benchmark.
+pub fn monitor_cpu(stop: &AtomicBool, interval: Duration) -> CpuStats {
+    let system = System::new();
+    let mut user_samples = Vec::new();
+    let mut system_samples = Vec::new();
+    while !stop.load(Ordering::Relaxed) {
+        match system.cpu_load_aggregate() {
+            Ok(measurement) => {
+                thread::sleep(interval);
+                if let Ok(cpu) = measurement.done() {
+                    user_samples.push(cpu.user as f64 * 100.0);
+                    system_samples.push(cpu.system as f64 * 100.0);
+                }
+            }
+            Err(_) => thread::sleep(interval),
+        }
+    }
+    CpuStats {
+        mean_user_pct: mean(&user_samples),
+        mean_system_pct: mean(&system_samples),
+    }
+}
+
+fn mean(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().sum::<f64>() / samples.len() as f64
+    }
+}
@@ -0,0 +1,112 @@
+// Key-access distributions, mirroring db_bench's key-distribution options: Uniform
+// spreads keys evenly across the keyspace, Sequential walks it in order, and Zipfian
+// concentrates accesses on a shrinking set of "hot" keys.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use fastrand::Rng;
+use crate::common::KEY_SIZE;
+
+#[derive(Debug, Clone, Copy)]
+pub enum KeyDist {
+    Uniform,
+    Sequential,
+    Zipfian { theta: f64 },
+}
+
+pub struct KeyDistState {
+    dist: KeyDist,
+    keyspace_size: u64,
+    sequential_cursor: u64,
+    zipfian: Option<ZipfianGenerator>,
+}
+
+impl KeyDistState {
+    pub fn new(dist: KeyDist, keyspace_size: u64) -> Self {
+        let zipfian = match dist {
+            KeyDist::Zipfian { theta } => Some(ZipfianGenerator::new(keyspace_size, theta)),
+            _ => None,
+        };
+        Self { dist, keyspace_size: keyspace_size.max(1), sequential_cursor: 0, zipfian }
+    }
+
+    pub fn gen_key(&mut self, rng: &mut Rng) -> Vec<u8> {
+        let rank = self.next_rank(rng);
+        encode_key(rank, matches!(self.dist, KeyDist::Sequential))
+    }
+
+    fn next_rank(&mut self, rng: &mut Rng) -> u64 {
+        match self.dist {
+            KeyDist::Uniform => rng.u64(0..self.keyspace_size),
+            KeyDist::Sequential => {
+                let rank = self.sequential_cursor % self.keyspace_size;
+                self.sequential_cursor += 1;
+                rank
+            }
+            KeyDist::Zipfian { .. } => self.zipfian.as_ref().unwrap().next(rng) - 1,
+        }
+    }
+}
+
+// encodes a rank into the KEY_SIZE keyspace. Sequential keys keep the rank's natural
+// order so range scans stay sequential; other distributions hash the rank so that
+// repeated draws of the same hot rank cluster on the same key.
+fn encode_key(rank: u64, sequential: bool) -> Vec<u8> {
+    let mut key = vec![0u8; KEY_SIZE];
+    if sequential {
+        let offset = KEY_SIZE.saturating_sub(8);
+        key[offset..].copy_from_slice(&rank.to_be_bytes()[8usize.saturating_sub(KEY_SIZE)..]);
+    } else {
+        let mut hasher = DefaultHasher::new();
+        rank.hash(&mut hasher);
+        let mut seed = hasher.finish();
+        for chunk in key.chunks_mut(8) {
+            let bytes = seed.to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+            seed = seed.wrapping_mul(0x9E3779B97F4A7C15).wrapping_add(1);
+        }
+    }
+    key
+}
+
+// standard Gray et al. zipfian generator
+struct ZipfianGenerator {
+    n: u64,
+    theta: f64,
+    alpha: f64,
+    zetan: f64,
+    eta: f64,
+}
+
+impl ZipfianGenerator {
+    fn new(n: u64, theta: f64) -> Self {
+        let n = n.max(2);
+        // theta == 1.0 makes alpha = 1/(1-theta) blow up; nudge it off the pole rather
+        // than special-casing the harmonic-series formula for a value nobody actually
+        // needs exactly.
+        let theta = if theta == 1.0 { 1.0 - 1e-9 } else { theta };
+        let zetan = zeta(n, theta);
+        let alpha = 1.0 / (1.0 - theta);
+        let zeta2 = zeta(2, theta);
+        let eta = (1.0 - (2.0 / n as f64).powf(1.0 - theta)) / (1.0 - zeta2 / zetan);
+        Self { n, theta, alpha, zetan, eta }
+    }
+
+    // returns a rank in [1, n]
+    fn next(&self, rng: &mut Rng) -> u64 {
+        let u = rng.f64();
+        let uz = u * self.zetan;
+        if uz < 1.0 {
+            return 1;
+        }
+        if uz < 1.0 + 2f64.powf(-self.theta) {
+            return 2;
+        }
+        let rank = (self.n as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha)) as u64;
+        rank.clamp(1, self.n)
+    }
+}
+
+fn zeta(n: u64, theta: f64) -> f64 {
+    (1..=n).map(|i| 1.0 / (i as f64).powf(theta)).sum()
+}
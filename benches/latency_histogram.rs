@@ -0,0 +1,84 @@
+// Fixed-bucket, log2-scaled latency histogram, similar in spirit to RocksDB's
+// db_bench histogram: cheap to record into (no allocation, no locking) so each
+// worker thread can keep its own and merge at the end of a step.
+
+const BUCKET_COUNT: usize = 64;
+
+pub struct LatencyHistogram {
+    // buckets[i] counts samples whose (refined) log2 bucket index is i
+    buckets: Vec<u64>,
+    count: u64,
+    max_micros: u64,
+    precision: usize,
+}
+
+impl LatencyHistogram {
+    pub fn new(precision: usize) -> Self {
+        let precision = precision.max(1);
+        Self {
+            buckets: vec![0; BUCKET_COUNT * precision],
+            count: 0,
+            max_micros: 0,
+            precision,
+        }
+    }
+
+    pub fn record(&mut self, micros: u64) {
+        let index = self.bucket_index(micros);
+        self.buckets[index] += 1;
+        self.count += 1;
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        assert_eq!(self.precision, other.precision);
+        for (dst, src) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *dst += src;
+        }
+        self.count += other.count;
+        self.max_micros = self.max_micros.max(other.max_micros);
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max_micros
+    }
+
+    // upper bound (in micros) of the bucket containing the q-th quantile, e.g. q=0.99 for p99
+    pub fn quantile(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = (q * self.count as f64).ceil() as u64;
+        let mut cumulative = 0u64;
+        for (index, &bucket_count) in self.buckets.iter().enumerate() {
+            cumulative += bucket_count;
+            if cumulative >= target {
+                return self.bucket_upper_bound(index);
+            }
+        }
+        self.max_micros
+    }
+
+    // splits each log2 octave of n = micros + 1 into `precision` equal-width linear
+    // sub-buckets, so e.g. with precision=4 the values n in [8, 16) (micros in [7, 15))
+    // land in 4 distinct buckets instead of all collapsing into the single
+    // "log_bucket == 3" bucket.
+    fn bucket_index(&self, micros: u64) -> usize {
+        let n = micros + 1;
+        let log_bucket = 63 - n.leading_zeros(); // floor(log2(n))
+        let octave_start = 1u64 << log_bucket;
+        let offset_in_octave = n - octave_start; // in [0, octave_start)
+        let sub_bucket = (offset_in_octave * self.precision as u64) / octave_start;
+        (log_bucket as usize * self.precision + sub_bucket as usize).min(self.buckets.len() - 1)
+    }
+
+    fn bucket_upper_bound(&self, index: usize) -> u64 {
+        let log_bucket = (index / self.precision) as u32;
+        let sub_bucket = (index % self.precision) as u64;
+        let octave_start = 1u64 << log_bucket;
+        let precision = self.precision as u64;
+        // rounded up: bucket_index() floors, so a sub-bucket holds offsets up to ceil(...) - 1
+        let n_exclusive_upper = octave_start + ((sub_bucket + 1) * octave_start + precision - 1) / precision;
+        n_exclusive_upper.saturating_sub(2) // convert back from n = micros + 1, exclusive -> inclusive
+    }
+}
@@ -0,0 +1,288 @@
+pub trait BenchDatabase {
+    type W<'db>: BenchWriteTransaction
+    where
+        Self: 'db;
+    type R<'db>: BenchReadTransaction
+    where
+        Self: 'db;
+
+    fn db_type_name() -> &'static str;
+    fn write_transaction(&self) -> Self::W<'_>;
+    fn read_transaction(&self) -> Self::R<'_>;
+    fn key_count(&self) -> usize;
+}
+
+pub trait BenchWriteTransaction {
+    type Inserter<'txn>: BenchInserter
+    where
+        Self: 'txn;
+
+    fn get_inserter(&mut self) -> Self::Inserter<'_>;
+    fn commit(self) -> Result<(), ()>;
+}
+
+pub trait BenchInserter {
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()>;
+}
+
+pub trait BenchReadTransaction {
+    type Reader<'txn>: BenchReader
+    where
+        Self: 'txn;
+
+    fn get_reader(&self) -> Self::Reader<'_>;
+}
+
+pub trait BenchReader {
+    fn range_from(&self, key: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_;
+}
+
+pub struct RocksdbBenchDatabase<'a> {
+    db: &'a rocksdb::TransactionDB,
+}
+
+impl<'a> RocksdbBenchDatabase<'a> {
+    pub fn new(db: &'a rocksdb::TransactionDB) -> Self {
+        Self { db }
+    }
+}
+
+impl<'a> BenchDatabase for RocksdbBenchDatabase<'a> {
+    type W<'db> = RocksdbBenchWriteTransaction<'db> where Self: 'db;
+    type R<'db> = RocksdbBenchReadTransaction<'db> where Self: 'db;
+
+    fn db_type_name() -> &'static str {
+        "rocksdb"
+    }
+
+    fn write_transaction(&self) -> Self::W<'_> {
+        RocksdbBenchWriteTransaction { tx: self.db.transaction() }
+    }
+
+    fn read_transaction(&self) -> Self::R<'_> {
+        RocksdbBenchReadTransaction { db: self.db }
+    }
+
+    fn key_count(&self) -> usize {
+        self.db.iterator(rocksdb::IteratorMode::Start).count()
+    }
+}
+
+pub struct RocksdbBenchWriteTransaction<'db> {
+    tx: rocksdb::Transaction<'db, rocksdb::TransactionDB>,
+}
+
+impl<'db> BenchWriteTransaction for RocksdbBenchWriteTransaction<'db> {
+    type Inserter<'txn> = &'txn rocksdb::Transaction<'db, rocksdb::TransactionDB> where Self: 'txn;
+
+    fn get_inserter(&mut self) -> Self::Inserter<'_> {
+        &self.tx
+    }
+
+    fn commit(self) -> Result<(), ()> {
+        self.tx.commit().map_err(|_| ())
+    }
+}
+
+impl<'db> BenchInserter for &'_ rocksdb::Transaction<'db, rocksdb::TransactionDB> {
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()> {
+        self.put(key, value).map_err(|_| ())
+    }
+}
+
+pub struct RocksdbBenchReadTransaction<'db> {
+    db: &'db rocksdb::TransactionDB,
+}
+
+impl<'db> BenchReadTransaction for RocksdbBenchReadTransaction<'db> {
+    type Reader<'txn> = &'txn rocksdb::TransactionDB where Self: 'txn;
+
+    fn get_reader(&self) -> Self::Reader<'_> {
+        self.db
+    }
+}
+
+impl BenchReader for &'_ rocksdb::TransactionDB {
+    fn range_from(&self, key: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.iterator(rocksdb::IteratorMode::From(key, rocksdb::Direction::Forward))
+            .filter_map(|result| result.ok())
+            .map(|(k, v)| (k.to_vec(), v.to_vec()))
+    }
+}
+
+pub struct HeedBenchDatabase<'a> {
+    env: &'a heed::Env,
+    database: heed::Database<heed::types::Bytes, heed::types::Bytes>,
+}
+
+impl<'a> HeedBenchDatabase<'a> {
+    pub fn new(env: &'a heed::Env) -> Self {
+        let mut wtxn = env.write_txn().unwrap();
+        let database = env.create_database(&mut wtxn, None).unwrap();
+        wtxn.commit().unwrap();
+        Self { env, database }
+    }
+}
+
+impl<'a> BenchDatabase for HeedBenchDatabase<'a> {
+    type W<'db> = HeedBenchWriteTransaction<'db> where Self: 'db;
+    type R<'db> = HeedBenchReadTransaction<'db> where Self: 'db;
+
+    fn db_type_name() -> &'static str {
+        "lmdb"
+    }
+
+    fn write_transaction(&self) -> Self::W<'_> {
+        HeedBenchWriteTransaction { txn: self.env.write_txn().unwrap(), database: self.database }
+    }
+
+    fn read_transaction(&self) -> Self::R<'_> {
+        HeedBenchReadTransaction { txn: self.env.read_txn().unwrap(), database: self.database }
+    }
+
+    fn key_count(&self) -> usize {
+        let rtxn = self.env.read_txn().unwrap();
+        self.database.len(&rtxn).unwrap() as usize
+    }
+}
+
+pub struct HeedBenchWriteTransaction<'db> {
+    txn: heed::RwTxn<'db>,
+    database: heed::Database<heed::types::Bytes, heed::types::Bytes>,
+}
+
+impl<'db> BenchWriteTransaction for HeedBenchWriteTransaction<'db> {
+    type Inserter<'txn> = HeedBenchInserter<'db, 'txn> where Self: 'txn;
+
+    fn get_inserter(&mut self) -> Self::Inserter<'_> {
+        HeedBenchInserter { txn: &mut self.txn, database: self.database }
+    }
+
+    fn commit(self) -> Result<(), ()> {
+        self.txn.commit().map_err(|_| ())
+    }
+}
+
+pub struct HeedBenchInserter<'db, 'txn> {
+    txn: &'txn mut heed::RwTxn<'db>,
+    database: heed::Database<heed::types::Bytes, heed::types::Bytes>,
+}
+
+impl<'db, 'txn> BenchInserter for HeedBenchInserter<'db, 'txn> {
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()> {
+        self.database.put(self.txn, key, value).map_err(|_| ())
+    }
+}
+
+pub struct HeedBenchReadTransaction<'db> {
+    txn: heed::RoTxn<'db>,
+    database: heed::Database<heed::types::Bytes, heed::types::Bytes>,
+}
+
+impl<'db> BenchReadTransaction for HeedBenchReadTransaction<'db> {
+    type Reader<'txn> = HeedBenchReader<'db, 'txn> where Self: 'txn;
+
+    fn get_reader(&self) -> Self::Reader<'_> {
+        HeedBenchReader { txn: &self.txn, database: self.database }
+    }
+}
+
+pub struct HeedBenchReader<'db, 'txn> {
+    txn: &'txn heed::RoTxn<'db>,
+    database: heed::Database<heed::types::Bytes, heed::types::Bytes>,
+}
+
+impl<'db, 'txn> BenchReader for HeedBenchReader<'db, 'txn> {
+    fn range_from(&self, key: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.database
+            .range(self.txn, &(key.to_vec()..))
+            .unwrap()
+            .filter_map(|result| result.ok())
+            .map(|(k, v)| (k.to_vec(), v.to_vec()))
+    }
+}
+
+// sled has no explicit transactions: a "write transaction" batches inserts and applies
+// them as a single sled::Batch on commit, and a "read transaction" just holds a
+// reference to the Db: its reads are not pinned to one snapshot, so they can see
+// batches applied after it was opened.
+//
+// Unlike LMDB (opened with NO_SYNC) and RocksDB (not opened with a sync-on-commit option),
+// sled is left to fsync on its own default background schedule rather than forced via an
+// explicit flush() here, so all three drivers are compared under the same "don't sync
+// every commit" durability policy instead of sled silently paying a per-commit fsync cost
+// the other two don't.
+pub struct SledBenchDatabase<'a> {
+    db: &'a sled::Db,
+}
+
+impl<'a> SledBenchDatabase<'a> {
+    pub fn new(db: &'a sled::Db) -> Self {
+        Self { db }
+    }
+}
+
+impl<'a> BenchDatabase for SledBenchDatabase<'a> {
+    type W<'db> = SledBenchWriteTransaction<'db> where Self: 'db;
+    type R<'db> = SledBenchReadTransaction<'db> where Self: 'db;
+
+    fn db_type_name() -> &'static str {
+        "sled"
+    }
+
+    fn write_transaction(&self) -> Self::W<'_> {
+        SledBenchWriteTransaction { db: self.db, batch: sled::Batch::default() }
+    }
+
+    fn read_transaction(&self) -> Self::R<'_> {
+        SledBenchReadTransaction { db: self.db }
+    }
+
+    fn key_count(&self) -> usize {
+        self.db.len()
+    }
+}
+
+pub struct SledBenchWriteTransaction<'db> {
+    db: &'db sled::Db,
+    batch: sled::Batch,
+}
+
+impl<'db> BenchWriteTransaction for SledBenchWriteTransaction<'db> {
+    type Inserter<'txn> = &'txn mut sled::Batch where Self: 'txn;
+
+    fn get_inserter(&mut self) -> Self::Inserter<'_> {
+        &mut self.batch
+    }
+
+    fn commit(self) -> Result<(), ()> {
+        self.db.apply_batch(self.batch).map_err(|_| ())
+    }
+}
+
+impl BenchInserter for &'_ mut sled::Batch {
+    fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()> {
+        self.insert(key, value);
+        Ok(())
+    }
+}
+
+pub struct SledBenchReadTransaction<'db> {
+    db: &'db sled::Db,
+}
+
+impl<'db> BenchReadTransaction for SledBenchReadTransaction<'db> {
+    type Reader<'txn> = &'txn sled::Db where Self: 'txn;
+
+    fn get_reader(&self) -> Self::Reader<'_> {
+        self.db
+    }
+}
+
+impl BenchReader for &'_ sled::Db {
+    fn range_from(&self, key: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.range(key.to_vec()..)
+            .filter_map(|result| result.ok())
+            .map(|(k, v)| (k.to_vec(), v.to_vec()))
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use crate::key_dist::KeyDist;
+
+// Uniform draws insert_key_total_count samples from a keyspace of this size. Birthday-paradox
+// collisions among those draws silently turn "insert a new key" into "overwrite an old one", so
+// the keyspace needs to be a large multiple of the draw count to keep near-unique-key semantics
+// (the pre-key_dist benchmark drew full-width random keys, which was collision-free in practice).
+const KEYSPACE_SIZE_MULTIPLIER: u64 = 1024;
+
+// a reasonable default for a small record value, e.g. a serialized struct or short string
+const DEFAULT_VALUE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy)]
+pub enum CompressionKind {
+    Zstd { level: i32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct OpSize {
+    pub insert_key_total_count: usize,
+    pub insert_key_per_tx_count: usize,
+    pub scan_total_count: usize,
+    pub scan_per_tx_count: usize,
+    pub iter_per_scan_count: usize,
+    // number of sub-buckets per power-of-two doubling in the latency histograms;
+    // higher values trade memory for tighter quantile estimates
+    pub latency_precision: usize,
+    // wall-clock duration of the mixed read-while-writing step
+    pub mixed_duration: Duration,
+    // access pattern used to pick keys for inserts and scans
+    pub key_dist: KeyDist,
+    // size of the keyspace that key_dist draws ranks from
+    pub keyspace_size: u64,
+    // size in bytes of each inserted value
+    pub value_size: usize,
+    // when set, values are compressed before being inserted
+    pub compression: Option<CompressionKind>,
+}
+
+impl OpSize {
+    pub fn new(
+        insert_key_total_count: usize,
+        insert_key_per_tx_count: usize,
+        scan_total_count: usize,
+        scan_per_tx_count: usize,
+        iter_per_scan_count: usize,
+    ) -> Self {
+        Self {
+            insert_key_total_count,
+            insert_key_per_tx_count,
+            scan_total_count,
+            scan_per_tx_count,
+            iter_per_scan_count,
+            latency_precision: 2,
+            mixed_duration: Duration::from_secs(30),
+            key_dist: KeyDist::Uniform,
+            keyspace_size: insert_key_total_count as u64 * KEYSPACE_SIZE_MULTIPLIER,
+            value_size: DEFAULT_VALUE_SIZE,
+            compression: None,
+        }
+    }
+}
@@ -1,38 +1,49 @@
 use byte_unit::rust_decimal::prelude::ToPrimitive;
-use std::fmt::Display;
 use std::path::Path;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 use fastrand::Rng;
-use tempfile::TempDir;
 
 use crate::common::*;
-
 use crate::storage_common::*;
 use crate::storage_op_size::OpSize;
+use crate::latency_histogram::LatencyHistogram;
+use crate::bench_report::BenchReporter;
+use crate::key_dist::KeyDistState;
+use crate::value_gen::{gen_value, maybe_compress};
+use crate::cpu_monitor::{monitor_cpu, CpuStats};
 
 const PRINT_FREQUENCY_SEC: Duration = Duration::new(2, 0);
 
-pub fn preload_step<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize) {
+pub fn preload_step<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, reporter: Option<&BenchReporter>) {
+    let logical_bytes = AtomicU64::new(0);
+    let cpu_stop = AtomicBool::new(false);
     let start = Instant::now();
-    thread::scope(|scope| {
-        for thread_id in 0..thread_count {
-            scope.spawn(move || preload_step_single_thread(driver, op_size, thread_count, thread_id));
-        }
+    let (histograms, cpu_stats): (Vec<LatencyHistogram>, CpuStats) = thread::scope(|scope| {
+        let cpu_handle = scope.spawn(|| monitor_cpu(&cpu_stop, PRINT_FREQUENCY_SEC));
+        let handles: Vec<_> = (0..thread_count)
+            .map(|thread_id| scope.spawn(|| preload_step_single_thread(driver, op_size, thread_count, thread_id, &logical_bytes)))
+            .collect();
+        let histograms = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        cpu_stop.store(true, Ordering::Relaxed);
+        (histograms, cpu_handle.join().unwrap())
     });
     let end = Instant::now();
     let duration = end - start;
-    print_preload_stats::<T>(op_size, duration);
+    let commit_latency = merge_histograms(histograms, op_size);
+    let logical_bytes = logical_bytes.load(Ordering::Relaxed);
+    print_preload_stats::<T>(op_size, duration, &commit_latency, logical_bytes, &cpu_stats, reporter);
 }
 
-fn preload_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, thread_id: usize) {
+fn preload_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, thread_id: usize, logical_bytes: &AtomicU64) -> LatencyHistogram {
     let mut rng = create_rng();
+    let mut key_dist = KeyDistState::new(op_size.key_dist, op_size.keyspace_size);
     let mut last_printed = Instant::now();
     let mut transactions = 0;
+    let mut commit_latency = LatencyHistogram::new(op_size.latency_precision);
     for _ in 0..(op_size.insert_key_total_count / op_size.insert_key_per_tx_count / thread_count) {
-        insert_keys(driver, op_size, &mut rng);
+        insert_keys(driver, op_size, &mut rng, &mut key_dist, &mut commit_latency, logical_bytes);
         transactions += 1;
         let time_since_last_print = Instant::now() - last_printed;
         if time_since_last_print > PRINT_FREQUENCY_SEC {
@@ -41,42 +52,55 @@ fn preload_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_siz
             transactions = 0;
         }
     }
+    commit_latency
 }
 
-fn insert_keys<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, mut rng: &mut Rng) {
+fn insert_keys<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, mut rng: &mut Rng, key_dist: &mut KeyDistState, commit_latency: &mut LatencyHistogram, logical_bytes: &AtomicU64) {
     let mut tx = driver.write_transaction();
     {
         let mut inserter = tx.get_inserter();
         for _ in 0..op_size.insert_key_per_tx_count {
-            let key = gen_key(&mut rng);
-            let value = Vec::new();
+            let key = gen_key(&mut rng, key_dist);
+            let value = gen_value(&mut rng, op_size.value_size);
+            logical_bytes.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+            let value = maybe_compress(value, op_size.compression);
             match inserter.insert(&key, &value) {
                 Ok(()) => {}
                 Err(()) => {}
             }
         }
     }
+    let commit_start = Instant::now();
     tx.commit().unwrap();
+    commit_latency.record(commit_start.elapsed().as_micros() as u64);
 }
 
-pub fn scan_step<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize) {
+pub fn scan_step<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, reporter: Option<&BenchReporter>) {
+    let cpu_stop = AtomicBool::new(false);
     let start = Instant::now();
-    thread::scope(|s| {
-        for thread_id in 0..thread_count {
-            s.spawn(move || scan_step_single_thread(driver, op_size, thread_count, thread_id));
-        }
+    let (histograms, cpu_stats): (Vec<LatencyHistogram>, CpuStats) = thread::scope(|s| {
+        let cpu_handle = s.spawn(|| monitor_cpu(&cpu_stop, PRINT_FREQUENCY_SEC));
+        let handles: Vec<_> = (0..thread_count)
+            .map(|thread_id| s.spawn(move || scan_step_single_thread(driver, op_size, thread_count, thread_id)))
+            .collect();
+        let histograms = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        cpu_stop.store(true, Ordering::Relaxed);
+        (histograms, cpu_handle.join().unwrap())
     });
     let end = Instant::now();
     let duration = end - start;
-    print_scan_stats::<T>(op_size, duration);
+    let scan_latency = merge_histograms(histograms, op_size);
+    print_scan_stats::<T>(op_size, duration, &scan_latency, &cpu_stats, reporter);
 }
 
-fn scan_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, thread_id: usize) {
+fn scan_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, thread_count: usize, thread_id: usize) -> LatencyHistogram {
     let mut last_printed = Instant::now();
     let mut transactions = 0;
     let mut rng = create_rng();
+    let mut key_dist = KeyDistState::new(op_size.key_dist, op_size.keyspace_size);
+    let mut scan_latency = LatencyHistogram::new(op_size.latency_precision);
     for _ in 0..(op_size.scan_total_count / op_size.scan_per_tx_count / thread_count) {
-        scan_keys(driver, op_size, &mut rng);
+        scan_keys(driver, op_size, &mut rng, &mut key_dist, &mut scan_latency);
         transactions += 1;
         let time_since_last_print = Instant::now() - last_printed;
         if time_since_last_print > PRINT_FREQUENCY_SEC {
@@ -85,15 +109,17 @@ fn scan_step_single_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size:
             transactions = 0;
         }
     }
+    scan_latency
 }
 
-fn scan_keys<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, mut rng: &mut Rng) {
+fn scan_keys<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, mut rng: &mut Rng, key_dist: &mut KeyDistState, scan_latency: &mut LatencyHistogram) {
     let tx = driver.read_transaction();
     {
         let reader = tx.get_reader();
         for _ in 0..op_size.scan_per_tx_count {
-            let key = gen_prefix(rng);
+            let key = gen_prefix(rng, key_dist);
             let mut scanned_key = 0;
+            let scan_start = Instant::now();
             let mut iter = reader.range_from(&key);
             for i in 0..op_size.iter_per_scan_count {
                 scanned_key += 1;
@@ -102,19 +128,130 @@ fn scan_keys<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, mut r
                     None => { break; }
                 }
             }
+            scan_latency.record(scan_start.elapsed().as_micros() as u64);
         }
     }
     drop(tx);
 }
 
-fn print_preload_stats<T: BenchDatabase + Send + Sync>(op_size: &OpSize, duration: Duration) {
+// runs `writer_thread_count` threads inserting keys and `reader_thread_count` threads
+// scanning keys concurrently for `op_size.mixed_duration`, to exercise read performance
+// while writes are in flight (the `readwhilewriting` scenario from db_bench)
+pub fn mixed_step<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, writer_thread_count: usize, reader_thread_count: usize, reporter: Option<&BenchReporter>) {
+    let stop = AtomicBool::new(false);
+    let writer_keys = AtomicU64::new(0);
+    let reader_keys = AtomicU64::new(0);
+    let start = Instant::now();
+    let (read_latency, cpu_stats): (LatencyHistogram, CpuStats) = thread::scope(|scope| {
+        let cpu_handle = scope.spawn(|| monitor_cpu(&stop, PRINT_FREQUENCY_SEC));
+        for _ in 0..writer_thread_count {
+            scope.spawn(|| mixed_writer_thread(driver, op_size, &stop, &writer_keys));
+        }
+        let reader_handles: Vec<_> = (0..reader_thread_count)
+            .map(|_| scope.spawn(|| mixed_reader_thread(driver, op_size, &stop, &reader_keys)))
+            .collect();
+        thread::sleep(op_size.mixed_duration);
+        stop.store(true, Ordering::Relaxed);
+        let mut merged = LatencyHistogram::new(op_size.latency_precision);
+        for handle in reader_handles {
+            merged.merge(&handle.join().unwrap());
+        }
+        (merged, cpu_handle.join().unwrap())
+    });
+    let duration = start.elapsed();
+    print_mixed_stats::<T>(duration, writer_keys.load(Ordering::Relaxed), reader_keys.load(Ordering::Relaxed), &read_latency, &cpu_stats, reporter);
+}
+
+fn mixed_writer_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, stop: &AtomicBool, writer_keys: &AtomicU64) {
+    let mut rng = create_rng();
+    let mut key_dist = KeyDistState::new(op_size.key_dist, op_size.keyspace_size);
+    let mut commit_latency = LatencyHistogram::new(op_size.latency_precision);
+    let logical_bytes = AtomicU64::new(0);
+    while !stop.load(Ordering::Relaxed) {
+        insert_keys(driver, op_size, &mut rng, &mut key_dist, &mut commit_latency, &logical_bytes);
+        writer_keys.fetch_add(op_size.insert_key_per_tx_count as u64, Ordering::Relaxed);
+    }
+}
+
+fn mixed_reader_thread<T: BenchDatabase + Send + Sync>(driver: &T, op_size: &OpSize, stop: &AtomicBool, reader_keys: &AtomicU64) -> LatencyHistogram {
+    let mut rng = create_rng();
+    let mut key_dist = KeyDistState::new(op_size.key_dist, op_size.keyspace_size);
+    let mut scan_latency = LatencyHistogram::new(op_size.latency_precision);
+    while !stop.load(Ordering::Relaxed) {
+        scan_keys(driver, op_size, &mut rng, &mut key_dist, &mut scan_latency);
+        reader_keys.fetch_add((op_size.scan_per_tx_count * op_size.iter_per_scan_count) as u64, Ordering::Relaxed);
+    }
+    scan_latency
+}
+
+fn metric_name<T: BenchDatabase>(suffix: &str) -> String {
+    format!("{}/{}", T::db_type_name().to_lowercase(), suffix)
+}
+
+fn print_mixed_stats<T: BenchDatabase + Send + Sync>(duration: Duration, writer_keys: u64, reader_keys: u64, read_latency: &LatencyHistogram, cpu_stats: &CpuStats, reporter: Option<&BenchReporter>) {
+    let seconds = duration.as_nanos().to_f64().unwrap() / 1_000_000_000.0;
+    let writer_throughput = writer_keys.to_f64().unwrap() / seconds;
+    let reader_throughput = reader_keys.to_f64().unwrap() / seconds;
     println!(
-        "{}: Preload done: loaded {} keys in {}ms ({} key/s).",
+        "{}: Mixed read-while-writing done: ran for {}ms, {} writer key/s, {} reader key/s",
+        T::db_type_name(),
+        duration.as_millis(),
+        writer_throughput as u64,
+        reader_throughput as u64,
+    );
+    print_latency_stats("mixed read", read_latency);
+    print_cpu_stats(cpu_stats, writer_keys + reader_keys, duration);
+    if let Some(reporter) = reporter {
+        reporter.record(&metric_name::<T>("mixed_writer_throughput"), "key/s", writer_throughput);
+        reporter.record(&metric_name::<T>("mixed_reader_throughput"), "key/s", reader_throughput);
+        reporter.record(&metric_name::<T>("mixed_read_p99_us"), "us", read_latency.quantile(0.99) as f64);
+    }
+}
+
+fn print_cpu_stats(cpu_stats: &CpuStats, keys: u64, duration: Duration) {
+    print!("  CPU: user={:.1}% system={:.1}%", cpu_stats.mean_user_pct, cpu_stats.mean_system_pct);
+    match cpu_stats.keys_per_cpu_second(keys, duration) {
+        Some(efficiency) => println!(", {} key/CPU-s", efficiency as u64),
+        None => println!(", key/CPU-s unavailable"),
+    }
+}
+
+fn merge_histograms(histograms: Vec<LatencyHistogram>, op_size: &OpSize) -> LatencyHistogram {
+    let mut merged = LatencyHistogram::new(op_size.latency_precision);
+    for histogram in &histograms {
+        merged.merge(histogram);
+    }
+    merged
+}
+
+fn print_latency_stats(label: &str, histogram: &LatencyHistogram) {
+    println!(
+        "  {} latency (us): p50={} p99={} p99.9={} max={}",
+        label,
+        histogram.quantile(0.50),
+        histogram.quantile(0.99),
+        histogram.quantile(0.999),
+        histogram.max(),
+    );
+}
+
+fn print_preload_stats<T: BenchDatabase + Send + Sync>(op_size: &OpSize, duration: Duration, commit_latency: &LatencyHistogram, logical_bytes: u64, cpu_stats: &CpuStats, reporter: Option<&BenchReporter>) {
+    let throughput = op_size.insert_key_total_count.to_f64().unwrap() / (duration.as_nanos().to_f64().unwrap() / 1000_000_000.0);
+    println!(
+        "{}: Preload done: loaded {} keys ({} logical bytes) in {}ms ({} key/s).",
         T::db_type_name(),
         op_size.insert_key_total_count,
+        logical_bytes,
         duration.as_millis(),
-        (op_size.insert_key_total_count.to_f64().unwrap() / (duration.as_nanos().to_f64().unwrap() / 1000_000_000.0)) as u64,
+        throughput as u64,
     );
+    print_latency_stats("commit", commit_latency);
+    print_cpu_stats(cpu_stats, op_size.insert_key_total_count as u64, duration);
+    if let Some(reporter) = reporter {
+        reporter.record(&metric_name::<T>("preload_throughput"), "key/s", throughput);
+        reporter.record(&metric_name::<T>("preload_commit_p99_us"), "us", commit_latency.quantile(0.99) as f64);
+        reporter.record(&metric_name::<T>("preload_logical_bytes"), "bytes", logical_bytes as f64);
+    }
 }
 
 fn print_insertion_speed(op_size: &OpSize, thread_id: usize, mut transactions: usize, time_since_last_print: Duration) {
@@ -129,13 +266,18 @@ fn print_insertion_speed(op_size: &OpSize, thread_id: usize, mut transactions: u
     );
 }
 
-fn print_scan_stats<T: BenchDatabase + Send + Sync>(op_size: &OpSize, duration: Duration) {
+fn print_scan_stats<T: BenchDatabase + Send + Sync>(op_size: &OpSize, duration: Duration, scan_latency: &LatencyHistogram, cpu_stats: &CpuStats, reporter: Option<&BenchReporter>) {
     println!(
         "{}: Scan done: {} scan ops in {}ms",
         T::db_type_name(),
         op_size.scan_total_count,
         duration.as_millis(),
     );
+    print_latency_stats("scan", scan_latency);
+    print_cpu_stats(cpu_stats, (op_size.scan_total_count * op_size.iter_per_scan_count) as u64, duration);
+    if let Some(reporter) = reporter {
+        reporter.record(&metric_name::<T>("scan_p99_us"), "us", scan_latency.quantile(0.99) as f64);
+    }
 }
 
 fn print_scan_speed(op_size: &OpSize, thread_id: usize, mut transactions: usize, time_since_last_print: Duration) {
@@ -150,8 +292,11 @@ fn print_scan_speed(op_size: &OpSize, thread_id: usize, mut transactions: usize,
     );
 }
 
-pub fn print_data_size<T: BenchDatabase + Send + Sync>(path: &Path, driver: &T) {
+pub fn print_data_size<T: BenchDatabase + Send + Sync>(path: &Path, driver: &T, reporter: Option<&BenchReporter>) {
     let size = database_size(path);
     println!("{}: Database size: {} bytes", T::db_type_name(), size);
     println!("{}: Database keys: {} keys", T::db_type_name(), T::key_count(driver));
+    if let Some(reporter) = reporter {
+        reporter.record(&metric_name::<T>("db_size_bytes"), "bytes", size as f64);
+    }
 }
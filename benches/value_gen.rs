@@ -0,0 +1,24 @@
+// Generates values with enough internal repetition to be compressible, unlike fully
+// random bytes, so the optional zstd pass has something to do (similar in spirit to
+// sled's zstd_compression_level benchmarks).
+
+use fastrand::Rng;
+
+use crate::storage_op_size::CompressionKind;
+
+const PATTERN_SIZE: usize = 64;
+
+pub fn gen_value(rng: &mut Rng, value_size: usize) -> Vec<u8> {
+    let mut pattern = vec![0u8; PATTERN_SIZE.min(value_size.max(1))];
+    for byte in pattern.iter_mut() {
+        *byte = rng.u8(..);
+    }
+    pattern.iter().cycle().take(value_size).copied().collect()
+}
+
+pub fn maybe_compress(value: Vec<u8>, compression: Option<CompressionKind>) -> Vec<u8> {
+    match compression {
+        None => value,
+        Some(CompressionKind::Zstd { level }) => zstd::bulk::compress(&value, level).unwrap(),
+    }
+}